@@ -0,0 +1,597 @@
+use std::io::{Read, Write};
+use thiserror::Error;
+
+const DEFAULT_ARRAY_SIZE: usize = 30000; // brainfuck spec defines a 30000 byte memory
+const ALLOC_BLOCK_SIZE: usize = 128; // growth increment for a growable tape
+
+// An error type to represent errors encountered during interpretation
+#[derive(Error, Debug)]
+pub enum InterpreterError {
+    #[error("Unmatched '['")]
+    UnmatchedBeginLoop(Vec<usize>),
+    #[error("Unmatched ']'")]
+    UnmatchedEndLoop(usize),
+    #[error("Memory pointer incremented above array size ({0})")]
+    MemPointerAboveBounds(usize),
+    #[error("Memory pointer decremented below 0")]
+    MemPointerBelowBounds,
+    #[error("No input given")]
+    NoInput,
+    #[error("Step limit of {0} exceeded")]
+    StepLimitExceeded(u64),
+}
+
+// Runtime-configurable tape behavior
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    // number of cells allocated for the tape
+    pub array_size: usize,
+    // when true, IncPoint past the last cell wraps to 0 and DecPoint below 0 wraps to the last
+    // cell, instead of raising a bounds error
+    pub wrapping_pointer: bool,
+    // when true, the tape starts at one ALLOC_BLOCK_SIZE block and grows by another block
+    // whenever IncPoint would run off the end, instead of erroring or wrapping; `array_size` is
+    // ignored in this mode
+    pub growable: bool,
+    // abort with StepLimitExceeded once this many instructions have been dispatched; None (the
+    // default) runs unbounded
+    pub max_steps: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            array_size: DEFAULT_ARRAY_SIZE,
+            wrapping_pointer: false,
+            growable: false,
+            max_steps: None,
+        }
+    }
+}
+
+// Subscriber to interpreter state changes, for tracing/visualization use cases that shouldn't be
+// entangled with the core interpreter loop
+pub trait StateObserver {
+    fn on_cell_change(&mut self, index: usize, value: u8);
+    fn on_pointer_move(&mut self, index: usize);
+    fn on_output(&mut self, byte: u8);
+}
+
+// struct representing the interpreter, including memory, memory pointer, instructions, and the
+// input/output handles instructions read from and write to
+pub struct Interpreter<R, W> {
+    instructions: Instructions,
+    memory: Vec<u8>,
+    mem_pointer: usize,
+    config: Config,
+    steps: u64,
+    reader: R,
+    writer: W,
+    observers: Vec<Box<dyn StateObserver>>,
+}
+
+impl<R: Read, W: Write> Interpreter<R, W> {
+    // Initialize an interpreter from parsed instructions and the handles it should read/write through
+    pub fn new(instructions: Instructions, reader: R, writer: W) -> Self {
+        Self::with_config(instructions, reader, writer, Config::default())
+    }
+
+    // Initialize an interpreter with a custom tape size / wrapping mode
+    pub fn with_config(instructions: Instructions, reader: R, writer: W, config: Config) -> Self {
+        assert!(
+            config.growable || config.array_size > 0,
+            "array_size must be greater than 0"
+        );
+        let initial_size = if config.growable {
+            ALLOC_BLOCK_SIZE
+        } else {
+            config.array_size
+        };
+        Self {
+            instructions,
+            memory: vec![0; initial_size],
+            mem_pointer: 0,
+            config,
+            steps: 0,
+            reader,
+            writer,
+            observers: Vec::new(),
+        }
+    }
+
+    // subscribe an observer to cell/pointer/output changes
+    pub fn add_observer(&mut self, observer: Box<dyn StateObserver>) {
+        self.observers.push(observer);
+    }
+
+    fn notify_cell_change(&mut self, index: usize, value: u8) {
+        for observer in &mut self.observers {
+            observer.on_cell_change(index, value);
+        }
+    }
+
+    fn notify_pointer_move(&mut self, index: usize) {
+        for observer in &mut self.observers {
+            observer.on_pointer_move(index);
+        }
+    }
+
+    fn notify_output(&mut self, byte: u8) {
+        for observer in &mut self.observers {
+            observer.on_output(byte);
+        }
+    }
+
+    // execute a single instruction
+    pub fn step(&mut self) -> Result<(), InterpreterError> {
+        if let Some(max_steps) = self.config.max_steps {
+            if self.steps >= max_steps {
+                return Err(InterpreterError::StepLimitExceeded(max_steps));
+            }
+        }
+        self.steps += 1;
+
+        match self.instructions.instructions[self.instructions.pointer] {
+            Instruction::IncPoint => {
+                if self.config.growable {
+                    if self.mem_pointer + 1 >= self.memory.len() {
+                        self.memory.resize(self.memory.len() + ALLOC_BLOCK_SIZE, 0);
+                    }
+                    self.mem_pointer += 1;
+                } else if self.mem_pointer < self.config.array_size - 1 {
+                    self.mem_pointer += 1;
+                } else if self.config.wrapping_pointer {
+                    self.mem_pointer = 0;
+                } else {
+                    return Err(InterpreterError::MemPointerAboveBounds(
+                        self.config.array_size,
+                    ));
+                }
+                self.notify_pointer_move(self.mem_pointer);
+            }
+            Instruction::DecPoint => {
+                if self.mem_pointer > 0 {
+                    self.mem_pointer -= 1;
+                } else if self.config.wrapping_pointer {
+                    self.mem_pointer = self.memory.len() - 1;
+                } else {
+                    return Err(InterpreterError::MemPointerBelowBounds);
+                }
+                self.notify_pointer_move(self.mem_pointer);
+            }
+            Instruction::IncValue => {
+                self.memory[self.mem_pointer] = self.memory[self.mem_pointer].wrapping_add(1);
+                self.notify_cell_change(self.mem_pointer, self.memory[self.mem_pointer]);
+            }
+            Instruction::DecValue => {
+                self.memory[self.mem_pointer] = self.memory[self.mem_pointer].wrapping_sub(1);
+                self.notify_cell_change(self.mem_pointer, self.memory[self.mem_pointer]);
+            }
+            Instruction::LoopBegin => {
+                if self.memory[self.mem_pointer] == 0 {
+                    self.instructions.pointer = self.instructions.jumps[self.instructions.pointer];
+                }
+            }
+            Instruction::LoopEnd => {
+                if self.memory[self.mem_pointer] != 0 {
+                    self.instructions.pointer = self.instructions.jumps[self.instructions.pointer];
+                }
+            }
+            Instruction::GetChar => {
+                let mut byte = [0u8; 1];
+                match self.reader.read(&mut byte) {
+                    Ok(1) => {
+                        self.memory[self.mem_pointer] = byte[0];
+                        self.notify_cell_change(self.mem_pointer, byte[0]);
+                    }
+                    _ => return Err(InterpreterError::NoInput),
+                }
+            }
+            Instruction::PutChar => {
+                let byte = self.memory[self.mem_pointer];
+                self.writer
+                    .write_all(&[byte])
+                    .expect("failed to write output");
+                self.notify_output(byte);
+            }
+
+            Instruction::Comment => {
+                // do nothing on comments
+            }
+        }
+        self.instructions.pointer += 1; // increment instruction pointer
+        Ok(())
+    }
+
+    // run instructions until EOF
+    pub fn run(&mut self) -> Result<(), InterpreterError> {
+        while self.instructions.pointer < self.instructions.instructions.len() {
+            self.step()?;
+        }
+        Ok(())
+    }
+}
+
+// Enumeration defining Instructions in BF
+#[derive(Debug)]
+pub enum Instruction {
+    IncPoint,
+    DecPoint,
+    IncValue,
+    DecValue,
+    LoopBegin,
+    LoopEnd,
+    GetChar,
+    PutChar,
+    Comment,
+}
+
+impl Instruction {
+    // map ASCII to BF instructions
+    fn new(instruction: u8) -> Self {
+        match instruction {
+            62 => Self::IncPoint,
+            60 => Self::DecPoint,
+            43 => Self::IncValue,
+            45 => Self::DecValue,
+            91 => Self::LoopBegin,
+            93 => Self::LoopEnd,
+            44 => Self::GetChar,
+            46 => Self::PutChar,
+            _ => Self::Comment,
+        }
+    }
+}
+
+// Instructions struct represents a set of instructions
+// includes a vector of instructions, a pointer to the current instruction, and a
+// precomputed table mapping each LoopBegin/LoopEnd index to its match
+pub struct Instructions {
+    instructions: Vec<Instruction>,
+    pointer: usize,
+    jumps: Vec<usize>,
+}
+
+impl Instructions {
+    // Initializes instructions based on spec, computing the bracket jump table up front so
+    // unmatched loops are reported before execution rather than mid-run
+    fn new(instructions: Vec<Instruction>) -> Result<Self, InterpreterError> {
+        let mut jumps = vec![0; instructions.len()];
+        let mut begin_stack = Vec::new();
+
+        for (index, instruction) in instructions.iter().enumerate() {
+            match instruction {
+                Instruction::LoopBegin => begin_stack.push(index),
+                Instruction::LoopEnd => match begin_stack.pop() {
+                    Some(begin) => {
+                        jumps[begin] = index;
+                        jumps[index] = begin;
+                    }
+                    None => return Err(InterpreterError::UnmatchedEndLoop(index)),
+                },
+                _ => (),
+            }
+        }
+
+        if !begin_stack.is_empty() {
+            return Err(InterpreterError::UnmatchedBeginLoop(begin_stack));
+        }
+
+        Ok(Instructions {
+            instructions,
+            pointer: 0,
+            jumps,
+        })
+    }
+}
+
+// An observer that logs each notified change to a writer, backing a `--trace` flag
+pub struct TraceObserver<W> {
+    writer: W,
+}
+
+impl<W: Write> TraceObserver<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> StateObserver for TraceObserver<W> {
+    fn on_cell_change(&mut self, index: usize, value: u8) {
+        let _ = writeln!(self.writer, "cell[{index}] = {value}");
+    }
+
+    fn on_pointer_move(&mut self, index: usize) {
+        let _ = writeln!(self.writer, "pointer -> {index}");
+    }
+
+    fn on_output(&mut self, byte: u8) {
+        let _ = writeln!(self.writer, "output {byte:#04x}");
+    }
+}
+
+// parse BF source into instructions, ready to hand to an Interpreter
+pub fn parse(source: &[u8]) -> Result<Instructions, InterpreterError> {
+    let instructions: Vec<Instruction> = source.iter().map(|x| Instruction::new(*x)).collect();
+
+    Instructions::new(instructions)
+}
+
+// true while `fragment` still owes one or more closing ']', i.e. a REPL should keep buffering
+// lines rather than evaluate yet
+pub fn awaiting_closing_bracket(fragment: &[u8]) -> bool {
+    let mut depth: i64 = 0;
+    for &byte in fragment {
+        match byte {
+            b'[' => depth += 1,
+            b']' => depth -= 1,
+            _ => (),
+        }
+    }
+    depth > 0
+}
+
+// A REPL session: evaluates one bracket-balanced fragment of source at a time against memory
+// and a pointer that persist across calls, so a program can be built up incrementally
+pub struct Repl<R, W> {
+    memory: Vec<u8>,
+    mem_pointer: usize,
+    config: Config,
+    steps: u64,
+    reader: R,
+    writer: W,
+}
+
+impl<R: Read, W: Write> Repl<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self::with_config(reader, writer, Config::default())
+    }
+
+    pub fn with_config(reader: R, writer: W, config: Config) -> Self {
+        assert!(
+            config.growable || config.array_size > 0,
+            "array_size must be greater than 0"
+        );
+        let initial_size = if config.growable {
+            ALLOC_BLOCK_SIZE
+        } else {
+            config.array_size
+        };
+        Self {
+            memory: vec![0; initial_size],
+            mem_pointer: 0,
+            config,
+            steps: 0,
+            reader,
+            writer,
+        }
+    }
+
+    // parse and run a fragment against the persistent memory/pointer, saving state back even if
+    // the fragment errors partway through
+    pub fn eval(&mut self, fragment: &[u8]) -> Result<(), InterpreterError> {
+        let instructions = parse(fragment)?;
+        let mut interpreter = Interpreter {
+            instructions,
+            memory: std::mem::take(&mut self.memory),
+            mem_pointer: self.mem_pointer,
+            config: self.config,
+            steps: self.steps,
+            reader: &mut self.reader,
+            writer: &mut self.writer,
+            observers: Vec::new(),
+        };
+
+        let result = interpreter.run();
+        self.memory = interpreter.memory;
+        self.mem_pointer = interpreter.mem_pointer;
+        self.steps = interpreter.steps;
+        result
+    }
+
+    // print the non-zero cells within `radius` of the pointer, for the `:dump` meta-command
+    pub fn dump(&mut self, radius: usize) -> std::io::Result<()> {
+        let start = self.mem_pointer.saturating_sub(radius);
+        let end = (self.mem_pointer + radius + 1).min(self.memory.len());
+
+        writeln!(self.writer, "pointer: {}", self.mem_pointer)?;
+        for (index, &value) in self.memory[start..end].iter().enumerate() {
+            if value != 0 {
+                let index = start + index;
+                let marker = if index == self.mem_pointer { " <-" } else { "" };
+                writeln!(self.writer, "  [{index}] = {value}{marker}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // helper to run a program against fixed input, returning captured output
+    fn run(source: &str, input: &[u8]) -> Vec<u8> {
+        let instructions = parse(source.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        let mut interpreter =
+            Interpreter::new(instructions, Cursor::new(input.to_vec()), &mut output);
+        interpreter.run().unwrap();
+        output
+    }
+
+    #[test]
+    fn echoes_input() {
+        assert_eq!(run(",.", b"a"), b"a");
+    }
+
+    #[test]
+    fn increments_and_outputs_cell() {
+        assert_eq!(run("+++++.", b""), [5]);
+    }
+
+    #[test]
+    fn skips_loop_body_when_cell_is_zero() {
+        assert_eq!(
+            run("[+++++++++++++++++++++++++++++++++++++++++++++++.]", b""),
+            Vec::<u8>::new()
+        );
+    }
+
+    #[test]
+    fn awaiting_closing_bracket_tracks_open_loops() {
+        assert!(awaiting_closing_bracket(b"++[>+"));
+        assert!(!awaiting_closing_bracket(b"++[>+<-]"));
+        assert!(!awaiting_closing_bracket(b"]"));
+    }
+
+    #[test]
+    fn repl_preserves_state_across_fragments() {
+        let mut output = Vec::new();
+        let mut repl = Repl::new(Cursor::new(Vec::new()), &mut output);
+        repl.eval(b"+++").unwrap();
+        repl.eval(b">++").unwrap();
+        repl.eval(b"<.>.").unwrap();
+        assert_eq!(output, [3, 2]);
+    }
+
+    #[test]
+    fn pointer_above_bounds_errors_by_default() {
+        let instructions = parse(b">>").unwrap();
+        let mut interpreter = Interpreter::with_config(
+            instructions,
+            Cursor::new(Vec::new()),
+            Vec::new(),
+            Config {
+                array_size: 2,
+                ..Config::default()
+            },
+        );
+        assert!(matches!(
+            interpreter.run(),
+            Err(InterpreterError::MemPointerAboveBounds(2))
+        ));
+    }
+
+    #[test]
+    fn pointer_wraps_when_configured() {
+        let instructions = parse(b">>+.").unwrap();
+        let mut output = Vec::new();
+        let mut interpreter = Interpreter::with_config(
+            instructions,
+            Cursor::new(Vec::new()),
+            &mut output,
+            Config {
+                array_size: 2,
+                wrapping_pointer: true,
+                ..Config::default()
+            },
+        );
+        interpreter.run().unwrap();
+        assert_eq!(output, [1]);
+    }
+
+    #[test]
+    fn growable_tape_extends_past_initial_block() {
+        let source = ">".repeat(ALLOC_BLOCK_SIZE + 1) + "+.";
+        let instructions = parse(source.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        let mut interpreter = Interpreter::with_config(
+            instructions,
+            Cursor::new(Vec::new()),
+            &mut output,
+            Config {
+                growable: true,
+                ..Config::default()
+            },
+        );
+        interpreter.run().unwrap();
+        assert_eq!(output, [1]);
+    }
+
+    #[test]
+    fn growable_tape_wraps_to_actual_length_not_array_size() {
+        let instructions = parse(b"<.").unwrap();
+        let mut output = Vec::new();
+        let mut interpreter = Interpreter::with_config(
+            instructions,
+            Cursor::new(Vec::new()),
+            &mut output,
+            Config {
+                growable: true,
+                wrapping_pointer: true,
+                ..Config::default()
+            },
+        );
+        interpreter.run().unwrap();
+        assert_eq!(output, [0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "array_size must be greater than 0")]
+    fn zero_array_size_is_rejected() {
+        let instructions = parse(b"").unwrap();
+        Interpreter::with_config(
+            instructions,
+            Cursor::new(Vec::new()),
+            Vec::new(),
+            Config {
+                array_size: 0,
+                ..Config::default()
+            },
+        );
+    }
+
+    #[test]
+    fn step_limit_aborts_runaway_loop() {
+        let instructions = parse(b"+[]").unwrap();
+        let mut interpreter = Interpreter::with_config(
+            instructions,
+            Cursor::new(Vec::new()),
+            Vec::new(),
+            Config {
+                max_steps: Some(10),
+                ..Config::default()
+            },
+        );
+        assert!(matches!(
+            interpreter.run(),
+            Err(InterpreterError::StepLimitExceeded(10))
+        ));
+    }
+
+    // forwards notifications into a shared log so the test can inspect them after the observer
+    // has been moved into the interpreter
+    struct RecordingObserver(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+    impl StateObserver for RecordingObserver {
+        fn on_cell_change(&mut self, index: usize, value: u8) {
+            self.0.borrow_mut().push(format!("cell[{index}] = {value}"));
+        }
+
+        fn on_pointer_move(&mut self, index: usize) {
+            self.0.borrow_mut().push(format!("pointer -> {index}"));
+        }
+
+        fn on_output(&mut self, byte: u8) {
+            self.0.borrow_mut().push(format!("output {byte}"));
+        }
+    }
+
+    #[test]
+    fn observer_is_notified_of_cell_changes_and_output() {
+        let instructions = parse(b"+++.").unwrap();
+        let mut output = Vec::new();
+        let mut interpreter = Interpreter::new(instructions, Cursor::new(Vec::new()), &mut output);
+        let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        interpreter.add_observer(Box::new(RecordingObserver(log.clone())));
+
+        interpreter.run().unwrap();
+
+        assert_eq!(
+            *log.borrow(),
+            vec!["cell[0] = 1", "cell[0] = 2", "cell[0] = 3", "output 3",]
+        );
+    }
+}
@@ -1,172 +1,68 @@
-use std::{env, error::Error, fs::File, io::Read};
-use thiserror::Error;
-
-const MEMSIZE: usize = 30000; // brainfuck spec defines a 30000 byte memory
-
-// An error type to represent errors encountered during interpretation
-#[derive(Error, Debug)]
-enum InterpreterError {
-    #[error("Unmatched ']'")]
-    UnmatchedBeginLoop(Vec<usize>),
-    #[error("Unmatched ']'")]
-    UnmatchedEndLoop(usize),
-    #[error("Memory pointer incremented above MEMSIZE={MEMSIZE}")]
-    MemPointerBelowBounds,
-    #[error("Memory pointer decremented below 0")]
-    MemPointerAboveBounds,
-    #[error("No input given")]
-    NoInput,
-}
-
-// struct representing current state machine, including memory, memory pointer, and instructions
-struct State {
-    instructions: Instructions,
-    memory: [u8; MEMSIZE],
-    mem_pointer: usize,
-}
-
-impl State {
-    // Initialize state based on spec
-    fn initialize(instructions: Instructions) -> Self {
-        Self {
-            instructions,
-            memory: [0; MEMSIZE],
-            mem_pointer: 0,
-        }
-    }
+use std::{
+    env,
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+};
 
-    // update state by interpreting current instruction
-    fn update_state(&mut self) -> Result<(), InterpreterError> {
-        match self.instructions.instructions[self.instructions.pointer] {
-            Instruction::IncPoint => {
-                if self.mem_pointer < MEMSIZE - 1 {
-                    self.mem_pointer += 1;
-                } else {
-                    return Err(InterpreterError::MemPointerAboveBounds);
-                }
-            }
-            Instruction::DecPoint => {
-                if self.mem_pointer > 0 {
-                    self.mem_pointer -= 1;
-                } else {
-                    return Err(InterpreterError::MemPointerBelowBounds);
-                }
-            }
-            Instruction::IncValue => {
-                self.memory[self.mem_pointer] = self.memory[self.mem_pointer].wrapping_add(1)
-            }
-            Instruction::DecValue => {
-                self.memory[self.mem_pointer] = self.memory[self.mem_pointer].wrapping_sub(1)
-            }
-            Instruction::LoopBegin => self.instructions.jump_stack.push(self.instructions.pointer),
-            Instruction::LoopEnd => {
-                match self.instructions.jump_stack.pop() {
-                    Some(pointer) => {
-                        if self.memory[self.mem_pointer] != 0 {
-                            self.instructions.pointer = pointer - 1; // subtract one because this fn adds one at end
-                        }
-                    }
-                    None => {
-                        return Err(InterpreterError::UnmatchedEndLoop(
-                            self.instructions.pointer,
-                        ))
-                    }
-                };
-            }
-            Instruction::GetChar => {
-                let input: Option<u8> = std::io::stdin()
-                    .bytes()
-                    .next()
-                    .and_then(|result| result.ok());
-                match input {
-                    Some(value) => self.memory[self.mem_pointer] = value,
-                    None => return Err(InterpreterError::NoInput),
-                }
-            }
-            Instruction::PutChar => {
-                print!("{}", self.memory[self.mem_pointer] as char)
-            }
+use brainfuck_interpreter::{
+    awaiting_closing_bracket, parse, Config, Interpreter, Repl, TraceObserver,
+};
 
-            Instruction::Comment => {
-                // do nothing on comments
-            }
-        }
-        self.instructions.pointer += 1; // increment instruction pointer
-        Ok(())
-    }
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (config, trace, path) = parse_args(&args);
 
-    // Update state until EOF
-    fn run_program(&mut self) -> Result<(), InterpreterError> {
-        while self.instructions.pointer < self.instructions.instructions.len() {
-            self.update_state()?;
-        }
-        if self.instructions.jump_stack.is_empty() {
-            Ok(())
-        } else {
-            Err(InterpreterError::UnmatchedBeginLoop(
-                self.instructions.jump_stack.clone(),
-            ))
-        }
+    match path {
+        Some(path) => run_file(path, config, trace),
+        None => run_repl(config),
     }
 }
 
-// Enumeration defining Instructions in BF
-#[derive(Debug)]
-enum Instruction {
-    IncPoint,
-    DecPoint,
-    IncValue,
-    DecValue,
-    LoopBegin,
-    LoopEnd,
-    GetChar,
-    PutChar,
-    Comment,
-}
-
-impl Instruction {
-    // map ASCII to BF instructions
-    fn new(instruction: u8) -> Self {
-        match instruction {
-            62 => Self::IncPoint,
-            60 => Self::DecPoint,
-            43 => Self::IncValue,
-            45 => Self::DecValue,
-            91 => Self::LoopBegin,
-            93 => Self::LoopEnd,
-            44 => Self::GetChar,
-            46 => Self::PutChar,
-            _ => Self::Comment,
+// split CLI args into tape configuration, the --trace flag, and the optional source file path
+fn parse_args(args: &[String]) -> (Config, bool, Option<&str>) {
+    let mut config = Config::default();
+    let mut trace = false;
+    let mut path = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--array-size" => {
+                let size = iter.next().expect("--array-size requires a value");
+                let size: usize = size.parse().expect("--array-size must be a number");
+                assert!(size > 0, "--array-size must be greater than 0");
+                config.array_size = size;
+            }
+            "--wrapping-pointer" => config.wrapping_pointer = true,
+            "--growable-tape" => config.growable = true,
+            "--max-steps" => {
+                let steps = iter.next().expect("--max-steps requires a value");
+                config.max_steps = Some(steps.parse().expect("--max-steps must be a number"));
+            }
+            "--trace" => trace = true,
+            _ => path = Some(arg.as_str()),
         }
     }
-}
 
-// Instructions struct represents a set of instructions
-// includes a vector of instructions, a pointer to the current instruction, and a stack of jumps (for loops)
-struct Instructions {
-    instructions: Vec<Instruction>,
-    pointer: usize,
-    jump_stack: Vec<usize>,
+    (config, trace, path)
 }
 
-impl Instructions {
-    // Initializes instructions based on spec
-    fn new(instructions: Vec<Instruction>) -> Self {
-        Instructions {
-            instructions,
-            pointer: 0,
-            jump_stack: Vec::new(),
-        }
+// run a program loaded from a file to completion
+fn run_file(path: &str, config: Config, trace: bool) -> Result<(), Box<dyn Error>> {
+    let instructions = get_instructions(path)?;
+    let mut interpreter = Interpreter::with_config(
+        instructions,
+        BufReader::new(io::stdin()),
+        io::stdout(),
+        config,
+    );
+
+    if trace {
+        interpreter.add_observer(Box::new(TraceObserver::new(io::stderr())));
     }
-}
-
-fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = env::args().collect();
-    assert!(args.len() > 1, "No file inputted");
 
-    let mut state = State::initialize(get_instructions(&args[1])?);
-
-    match state.run_program() {
+    match interpreter.run() {
         Err(error) => {
             println!("\nInterpreter Error: {error}")
         }
@@ -178,12 +74,48 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-// read instructions from file
-fn get_instructions(input_file: &str) -> Result<Instructions, Box<dyn Error>> {
+// read and parse instructions from file
+fn get_instructions(
+    input_file: &str,
+) -> Result<brainfuck_interpreter::Instructions, Box<dyn Error>> {
     let mut input: Vec<u8> = Vec::new();
     File::open(input_file)?.read_to_end(&mut input)?;
 
-    let instructions: Vec<Instruction> = input.iter().map(|x| Instruction::new(*x)).collect();
+    Ok(parse(&input)?)
+}
+
+// interactively read BF source a line at a time, keeping memory and the memory pointer intact
+// between entries
+fn run_repl(config: Config) -> Result<(), Box<dyn Error>> {
+    let stdin = io::stdin();
+    let mut repl = Repl::with_config(io::stdin(), io::stdout(), config);
+    let mut pending = String::new();
 
-    Ok(Instructions::new(instructions))
+    loop {
+        print!("{}", if pending.is_empty() { "bf> " } else { "...> " });
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break; // EOF
+        }
+
+        if pending.is_empty() && line.trim() == ":dump" {
+            repl.dump(8)?;
+            continue;
+        }
+
+        pending.push_str(&line);
+
+        if awaiting_closing_bracket(pending.as_bytes()) {
+            continue;
+        }
+
+        if let Err(error) = repl.eval(pending.as_bytes()) {
+            println!("Interpreter Error: {error}");
+        }
+        pending.clear();
+    }
+
+    Ok(())
 }